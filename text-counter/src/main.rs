@@ -1,8 +1,14 @@
 use clap::Parser;
+use ignore::overrides::OverrideBuilder;
+use ignore::types::TypesBuilder;
+use ignore::WalkBuilder;
+use regex::Regex;
+use regex::RegexBuilder;
 use std::fs;
 use std::io::Read;
-use std::path::PathBuf;
-use walkdir::WalkDir;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
 
 #[derive(Parser)]
 #[command(name = "text-counter")]
@@ -17,40 +23,323 @@ struct Args {
     /// Make search case-insensitive
     #[arg(short = 'i', long = "case-insensitive")]
     case_insensitive: bool,
+
+    /// Treat the pattern as a regular expression instead of a literal substring
+    #[arg(short = 'e', long = "regex")]
+    regex: bool,
+
+    /// Emit newline-delimited JSON instead of human-readable text
+    #[arg(long = "json")]
+    json: bool,
+
+    /// Number of worker threads to use for counting (defaults to the number of CPUs)
+    #[arg(short = 'j', long = "threads")]
+    threads: Option<usize>,
+
+    /// Don't respect .gitignore/.ignore files when walking directories
+    #[arg(long = "no-ignore")]
+    no_ignore: bool,
+
+    /// Include hidden files and directories
+    #[arg(long = "hidden")]
+    hidden: bool,
+
+    /// Only search files of this type (e.g. "rust", "py"); may be repeated
+    #[arg(short = 't', long = "type")]
+    file_types: Vec<String>,
+
+    /// Only search files matching this glob pattern; may be repeated
+    #[arg(short = 'g', long = "glob")]
+    globs: Vec<String>,
+
+    /// Force a specific encoding instead of BOM-sniffing ("utf-8", "utf-16le",
+    /// "utf-16be", "utf-32le", "utf-32be")
+    #[arg(long = "encoding")]
+    encoding: Option<String>,
+
+    /// Treat every file as text, skipping the binary sniff entirely
+    #[arg(long = "text", conflicts_with = "binary")]
+    text: bool,
+
+    /// Search binary files too, but stop counting at the first NULL byte
+    #[arg(long = "binary", conflicts_with = "text")]
+    binary: bool,
+
+    /// Number of bytes to sample when sniffing whether a file is text
+    #[arg(long = "sniff-bytes", default_value_t = 8192)]
+    sniff_bytes: usize,
+
+    /// Record and print the line numbers where matches occur
+    #[arg(short = 'n', long = "line-numbers")]
+    line_numbers: bool,
+}
+
+/// How `search_directories` should treat files it can't confirm are text.
+#[derive(Clone, Copy)]
+enum BinaryPolicy {
+    /// Sniff each file and skip it if it looks binary (the default).
+    Skip,
+    /// Treat every file as text, no sniffing.
+    ForceText,
+    /// Search every file, but stop counting at the first NULL byte.
+    QuitAtNull,
+}
+
+impl BinaryPolicy {
+    fn from_args(args: &Args) -> Self {
+        if args.text {
+            BinaryPolicy::ForceText
+        } else if args.binary {
+            BinaryPolicy::QuitAtNull
+        } else {
+            BinaryPolicy::Skip
+        }
+    }
+}
+
+/// Selects how results are rendered once the search is done.
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    fn from_args(args: &Args) -> Self {
+        if args.json {
+            OutputFormat::Json
+        } else {
+            OutputFormat::Text
+        }
+    }
+}
+
+/// The compiled matcher used to count occurrences in a file, built once in
+/// `main` so directories aren't re-parsing the pattern per file.
+enum Matcher {
+    /// `pattern` is pre-lowercased at construction time when `case_insensitive`
+    /// is set, so each scanned line only has to lowercase itself, not the
+    /// pattern too.
+    Literal { pattern: String, case_insensitive: bool },
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn new(pattern: &str, case_insensitive: bool, use_regex: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        if use_regex {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(case_insensitive)
+                .build()?;
+            Ok(Matcher::Regex(regex))
+        } else {
+            let pattern = if case_insensitive { pattern.to_lowercase() } else { pattern.to_string() };
+            Ok(Matcher::Literal {
+                pattern,
+                case_insensitive,
+            })
+        }
+    }
+
+    /// Counts matches within a single line, returning `(count, matched_bytes)`.
+    fn count_in_line(&self, line: &str) -> (usize, usize) {
+        match self {
+            Matcher::Regex(regex) => {
+                let mut count = 0;
+                let mut matched_bytes = 0;
+                for m in regex.find_iter(line) {
+                    count += 1;
+                    matched_bytes += m.len();
+                }
+                (count, matched_bytes)
+            }
+            Matcher::Literal { pattern, case_insensitive } => {
+                if *case_insensitive {
+                    let line_lower = line.to_lowercase();
+                    let mut count = 0;
+                    let mut matched_bytes = 0;
+                    for (_, m) in line_lower.match_indices(pattern.as_str()) {
+                        count += 1;
+                        matched_bytes += m.len();
+                    }
+                    (count, matched_bytes)
+                } else {
+                    let count = line.matches(pattern.as_str()).count();
+                    (count, count * pattern.len())
+                }
+            }
+        }
+    }
 }
 
 struct FileResult {
     path: PathBuf,
     count: usize,
+    lines_scanned: usize,
+    matching_lines: usize,
+    matched_bytes: usize,
+    /// 1-indexed line numbers containing at least one match. Only populated
+    /// when `-n/--line-numbers` is passed.
+    match_line_numbers: Vec<usize>,
 }
 
-/// Check if a file is a text file by examining its content, similar to grep/ripgrep
-/// Reads the first 8KB of the file and checks for binary indicators
-fn is_text_file(path: &PathBuf) -> bool {
-    // Try to open and read the file
-    let mut file = match fs::File::open(path) {
-        Ok(f) => f,
-        Err(_) => return false, // If we can't open it, skip it
-    };
+impl FileResult {
+    /// Renders this result as a `{"type":"match",...}` JSON Lines record.
+    fn to_json_line(&self) -> String {
+        let line_numbers = self
+            .match_line_numbers
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
 
-    // Read first 8KB (8192 bytes) to check if it's text
-    // This is similar to what ripgrep does
-    let mut buffer = vec![0u8; 8192];
-    let bytes_read = match file.read(&mut buffer) {
-        Ok(n) => n,
-        Err(_) => return false,
-    };
+        format!(
+            "{{\"type\":\"match\",\"path\":{},\"count\":{},\"lines_scanned\":{},\"matching_lines\":{},\"matched_bytes\":{},\"line_numbers\":[{}]}}",
+            json_escape(&self.path.display().to_string()),
+            self.count,
+            self.lines_scanned,
+            self.matching_lines,
+            self.matched_bytes,
+            line_numbers,
+        )
+    }
+}
 
-    if bytes_read == 0 {
-        // Empty file is considered text
-        return true;
+/// Minimal JSON string escaping so we don't need a full serde dependency
+/// just to print paths and summary fields.
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
     }
+    escaped.push('"');
+    escaped
+}
 
-    // Trim buffer to actual bytes read
-    buffer.truncate(bytes_read);
+/// Wide text encodings we detect via BOM (or an explicit `--encoding`
+/// override) before falling back to the NULL-byte binary heuristic.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WideEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl WideEncoding {
+    fn from_label(label: &str) -> Option<Self> {
+        match label.to_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(WideEncoding::Utf8),
+            "utf-16le" | "utf16le" => Some(WideEncoding::Utf16Le),
+            "utf-16be" | "utf16be" => Some(WideEncoding::Utf16Be),
+            "utf-32le" | "utf32le" => Some(WideEncoding::Utf32Le),
+            "utf-32be" | "utf32be" => Some(WideEncoding::Utf32Be),
+            _ => None,
+        }
+    }
+}
+
+/// Sniffs a byte-order mark at the start of `buffer`, the same way
+/// text editors and `file(1)` detect Windows-origin UTF-16/UTF-32 text.
+fn detect_bom(buffer: &[u8]) -> Option<WideEncoding> {
+    if buffer.starts_with(&[0xFF, 0xFE, 0x00, 0x00]) {
+        Some(WideEncoding::Utf32Le)
+    } else if buffer.starts_with(&[0x00, 0x00, 0xFE, 0xFF]) {
+        Some(WideEncoding::Utf32Be)
+    } else if buffer.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        Some(WideEncoding::Utf8)
+    } else if buffer.starts_with(&[0xFF, 0xFE]) {
+        Some(WideEncoding::Utf16Le)
+    } else if buffer.starts_with(&[0xFE, 0xFF]) {
+        Some(WideEncoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+/// The length, in bytes, of `encoding`'s BOM if `bytes` actually starts with
+/// it. Zero if `bytes` has no such prefix (e.g. a forced encoding applied to
+/// BOM-less input).
+fn bom_len(encoding: WideEncoding, bytes: &[u8]) -> usize {
+    match encoding {
+        WideEncoding::Utf8 if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) => 3,
+        WideEncoding::Utf16Le if bytes.starts_with(&[0xFF, 0xFE]) => 2,
+        WideEncoding::Utf16Be if bytes.starts_with(&[0xFE, 0xFF]) => 2,
+        WideEncoding::Utf32Le if bytes.starts_with(&[0xFF, 0xFE, 0x00, 0x00]) => 4,
+        WideEncoding::Utf32Be if bytes.starts_with(&[0x00, 0x00, 0xFE, 0xFF]) => 4,
+        _ => 0,
+    }
+}
+
+/// Transcodes raw file bytes to a `String`, using `forced_encoding` if given
+/// or else BOM-sniffing. UTF-16 is decoded via `encoding_rs`; UTF-32, which
+/// `encoding_rs` doesn't support, is decoded by hand four bytes at a time.
+/// The BOM itself (if present) is stripped before transcoding so it doesn't
+/// leak into the decoded content as a leading U+FEFF character.
+fn transcode_to_utf8(
+    bytes: &[u8],
+    forced_encoding: Option<WideEncoding>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let encoding = forced_encoding.or_else(|| detect_bom(bytes)).unwrap_or(WideEncoding::Utf8);
+    let bytes = &bytes[bom_len(encoding, bytes)..];
+
+    match encoding {
+        WideEncoding::Utf8 => Ok(String::from_utf8(bytes.to_vec())?),
+        WideEncoding::Utf16Le => {
+            let (text, _, _) = encoding_rs::UTF_16LE.decode(bytes);
+            Ok(text.into_owned())
+        }
+        WideEncoding::Utf16Be => {
+            let (text, _, _) = encoding_rs::UTF_16BE.decode(bytes);
+            Ok(text.into_owned())
+        }
+        WideEncoding::Utf32Le => Ok(decode_utf32(bytes, u32::from_le_bytes)),
+        WideEncoding::Utf32Be => Ok(decode_utf32(bytes, u32::from_be_bytes)),
+    }
+}
+
+fn decode_utf32(bytes: &[u8], from_bytes: fn([u8; 4]) -> u32) -> String {
+    bytes
+        .chunks_exact(4)
+        .filter_map(|chunk| {
+            let code_point = from_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            char::from_u32(code_point)
+        })
+        .collect()
+}
+
+/// The result of sniffing whether a file is text or binary.
+enum TextKind {
+    Text,
+    Binary,
+}
+
+/// Classifies an in-memory sample as text or binary, the same way
+/// `sniff_text_kind` classifies a file sample. Shared so stdin's buffer can
+/// be sniffed without writing it to disk first.
+fn classify_text_kind(buffer: &[u8]) -> TextKind {
+    if buffer.is_empty() {
+        // Empty input is considered text
+        return TextKind::Text;
+    }
+
+    // A recognized BOM means this is wide-encoded text (UTF-16/UTF-32), which
+    // is full of NULL bytes by design, so it must be accepted before the
+    // NULL-byte veto below would otherwise reject it.
+    if detect_bom(buffer).is_some() {
+        return TextKind::Text;
+    }
 
     // Check for NULL byte (\0) - this is a strong indicator of binary files
-    // 
+    //
     // Why NULL bytes indicate binary files:
     // 1. Text files (UTF-8, ASCII, etc.) use NULL only as string terminator in memory,
     //    but actual text content should never contain NULL bytes
@@ -63,11 +352,10 @@ fn is_text_file(path: &PathBuf) -> bool {
     // 3. This heuristic is used by grep, ripgrep, and many Unix tools
     //    because it's fast and has very few false positives
     //
-    // Edge cases where text files might have NULL:
-    // - Very rare: UTF-16/UTF-32 text files (but these are uncommon)
-    // - Malformed text files (which we probably don't want to search anyway)
+    // BOM-less wide-encoded text would also trip this veto, but that's
+    // indistinguishable from genuine binary data without a BOM to go on.
     if buffer.contains(&0) {
-        return false;
+        return TextKind::Binary;
     }
 
     // Count non-printable characters (excluding common whitespace)
@@ -80,32 +368,154 @@ fn is_text_file(path: &PathBuf) -> bool {
 
     // If more than 5% of bytes are non-printable (excluding common whitespace),
     // consider it binary
-    let threshold = (bytes_read as f64 * 0.05) as usize;
-    non_printable_count <= threshold
+    let threshold = (buffer.len() as f64 * 0.05) as usize;
+    if non_printable_count <= threshold {
+        TextKind::Text
+    } else {
+        TextKind::Binary
+    }
 }
 
-fn count_pattern_in_file(path: &PathBuf, pattern: &str, case_insensitive: bool) -> Result<usize, Box<dyn std::error::Error>> {
-    let content = fs::read_to_string(path)?;
-    
-    let count = if case_insensitive {
-        let pattern_lower = pattern.to_lowercase();
-        let content_lower = content.to_lowercase();
-        content_lower.matches(&pattern_lower).count()
-    } else {
-        content.matches(pattern).count()
+/// Check if a file is a text file by examining its content, similar to grep/ripgrep
+/// Reads the first `sniff_bytes` of the file and checks for binary indicators
+fn sniff_text_kind(path: &PathBuf, sniff_bytes: usize) -> TextKind {
+    // Try to open and read the file
+    let mut file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(_) => return TextKind::Binary, // If we can't open it, skip it
+    };
+
+    // Read the sample window (8KB by default) to check if it's text
+    // This is similar to what ripgrep does
+    let mut buffer = vec![0u8; sniff_bytes];
+    let bytes_read = match file.read(&mut buffer) {
+        Ok(n) => n,
+        Err(_) => return TextKind::Binary,
     };
-    
-    Ok(count)
+    buffer.truncate(bytes_read);
+
+    classify_text_kind(&buffer)
 }
 
-fn search_directories(
+/// Per-file match statistics, mirroring ripgrep's `Stats`.
+struct LineStats {
+    count: usize,
+    lines_scanned: usize,
+    matching_lines: usize,
+    matched_bytes: usize,
+    match_line_numbers: Vec<usize>,
+}
+
+/// Counts matches in an in-memory buffer, the shared core used for both
+/// file-backed and stdin-backed searches. `display_name` is only used to
+/// label errors (e.g. `<stdin>` or a file path).
+fn count_pattern_in_bytes(
+    display_name: &str,
+    bytes: &[u8],
+    matcher: &Matcher,
+    forced_encoding: Option<WideEncoding>,
+    quit_at_null: bool,
+    track_line_numbers: bool,
+) -> Result<LineStats, Box<dyn std::error::Error>> {
+    let mut bytes = bytes.to_vec();
+    if let Some(offset) = quit_at_null.then(|| bytes.iter().position(|&b| b == 0)).flatten() {
+        bytes.truncate(offset);
+    }
+    let content = transcode_to_utf8(&bytes, forced_encoding)
+        .map_err(|e| format!("{}: {}", display_name, e))?;
+
+    let mut stats = LineStats {
+        count: 0,
+        lines_scanned: 0,
+        matching_lines: 0,
+        matched_bytes: 0,
+        match_line_numbers: Vec::new(),
+    };
+
+    for (index, line) in content.lines().enumerate() {
+        stats.lines_scanned += 1;
+        let (line_count, line_bytes) = matcher.count_in_line(line);
+        if line_count > 0 {
+            stats.matching_lines += 1;
+            stats.count += line_count;
+            stats.matched_bytes += line_bytes;
+            if track_line_numbers {
+                stats.match_line_numbers.push(index + 1);
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Reads a file's raw bytes, treating `/dev/null` as empty input even on
+/// platforms (like Windows) where the path doesn't physically exist, so the
+/// tool composes cleanly in shell pipelines and diff-style tooling.
+fn read_input_bytes(path: &PathBuf) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    if path.as_path() == Path::new("/dev/null") {
+        Ok(Vec::new())
+    } else {
+        Ok(fs::read(path)?)
+    }
+}
+
+fn count_pattern_in_file(
+    path: &PathBuf,
+    matcher: &Matcher,
+    forced_encoding: Option<WideEncoding>,
+    quit_at_null: bool,
+    track_line_numbers: bool,
+) -> Result<LineStats, Box<dyn std::error::Error>> {
+    let bytes = read_input_bytes(path)?;
+    count_pattern_in_bytes(
+        &path.display().to_string(),
+        &bytes,
+        matcher,
+        forced_encoding,
+        quit_at_null,
+        track_line_numbers,
+    )
+}
+
+/// Filters applied while walking directories: gitignore handling, hidden
+/// files, and the `-t`/`-g` file-type and glob restrictions.
+struct WalkFilters {
+    no_ignore: bool,
+    hidden: bool,
+    file_types: Vec<String>,
+    globs: Vec<String>,
+}
+
+impl WalkFilters {
+    fn from_args(args: &Args) -> Self {
+        WalkFilters {
+            no_ignore: args.no_ignore,
+            hidden: args.hidden,
+            file_types: args.file_types.clone(),
+            globs: args.globs.clone(),
+        }
+    }
+}
+
+/// Walks `directories` on the calling thread to build the candidate file
+/// list, honoring `.gitignore`/`.ignore` and the `-t`/`-g` filters the way
+/// ripgrep does. This part is cheap (just `stat`s) so it isn't worth
+/// parallelizing.
+fn collect_candidate_files(
     directories: &[PathBuf],
-    pattern: &str,
-    case_insensitive: bool,
-) -> Result<Vec<FileResult>, Box<dyn std::error::Error>> {
-    let mut results = Vec::new();
+    filters: &WalkFilters,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut files = Vec::new();
 
+    let mut existing_directories = Vec::new();
     for directory in directories {
+        if directory.as_path() == Path::new("/dev/null") {
+            // Flows through like any other candidate file; `read_input_bytes`
+            // is what actually treats it as empty input.
+            files.push(directory.clone());
+            continue;
+        }
+
         if !directory.exists() {
             eprintln!("Warning: Directory does not exist: {}", directory.display());
             continue;
@@ -116,68 +526,401 @@ fn search_directories(
             continue;
         }
 
-        for entry in WalkDir::new(directory).into_iter().filter_map(|e| e.ok()) {
-            let path = entry.path().to_path_buf();
-            
-            if path.is_file() {
-                // First check if it's a text file by content
-                if !is_text_file(&path) {
-                    continue; // Skip binary files
-                }
+        existing_directories.push(directory);
+    }
+
+    let Some((first, rest)) = existing_directories.split_first() else {
+        return Ok(files);
+    };
+
+    let mut types_builder = TypesBuilder::new();
+    types_builder.add_defaults();
+    for file_type in &filters.file_types {
+        types_builder.select(file_type);
+    }
+    let types = types_builder.build()?;
+
+    let mut overrides_builder = OverrideBuilder::new(first);
+    for glob in &filters.globs {
+        overrides_builder.add(glob)?;
+    }
+    let overrides = overrides_builder.build()?;
+
+    let mut builder = WalkBuilder::new(first);
+    for directory in rest {
+        builder.add(directory);
+    }
+    builder
+        .git_ignore(!filters.no_ignore)
+        .git_global(!filters.no_ignore)
+        .git_exclude(!filters.no_ignore)
+        .ignore(!filters.no_ignore)
+        .hidden(!filters.hidden)
+        .types(types)
+        .overrides(overrides);
+
+    for entry in builder.build().filter_map(|e| e.ok()) {
+        let path = entry.path().to_path_buf();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Bundles the per-run scan knobs that would otherwise be a long parameter
+/// list threaded through `search_directories` and its worker closures.
+#[derive(Clone, Copy)]
+struct SearchOptions {
+    num_threads: usize,
+    forced_encoding: Option<WideEncoding>,
+    binary_policy: BinaryPolicy,
+    sniff_bytes: usize,
+    track_line_numbers: bool,
+}
+
+/// Counts `matcher` across `directories` using a pool of worker threads, one
+/// per `num_threads`, each independently sniffing and counting its share of
+/// the candidate files. Results are sorted by path before returning so
+/// output is deterministic regardless of thread scheduling.
+fn search_directories(
+    directories: &[PathBuf],
+    matcher: &Matcher,
+    filters: &WalkFilters,
+    options: SearchOptions,
+) -> Result<Vec<FileResult>, Box<dyn std::error::Error>> {
+    let files = collect_candidate_files(directories, filters)?;
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let num_threads = options.num_threads.max(1).min(files.len().max(1));
 
-                match count_pattern_in_file(&path, pattern, case_insensitive) {
-                    Ok(count) => {
-                        if count > 0 {
-                            results.push(FileResult { path, count });
+    thread::scope(|scope| {
+        for chunk in files.chunks(files.len().div_ceil(num_threads).max(1)) {
+            let results = Arc::clone(&results);
+            scope.spawn(move || {
+                for path in chunk {
+                    // Decide whether to search this file at all, per the binary policy.
+                    // An explicit `--encoding` override means the user already told us
+                    // this is text, so it skips the sniff just like `--text` does.
+                    let should_search = match options.binary_policy {
+                        BinaryPolicy::ForceText | BinaryPolicy::QuitAtNull => true,
+                        BinaryPolicy::Skip => {
+                            options.forced_encoding.is_some()
+                                || matches!(sniff_text_kind(path, options.sniff_bytes), TextKind::Text)
                         }
+                    };
+                    if !should_search {
+                        continue; // Skip binary files
                     }
-                    Err(e) => {
-                        eprintln!("Error reading file {}: {}", path.display(), e);
+                    let quit_at_null = matches!(options.binary_policy, BinaryPolicy::QuitAtNull);
+
+                    match count_pattern_in_file(
+                        path,
+                        matcher,
+                        options.forced_encoding,
+                        quit_at_null,
+                        options.track_line_numbers,
+                    ) {
+                        Ok(stats) => {
+                            if stats.count > 0 {
+                                results.lock().unwrap().push(FileResult {
+                                    path: path.clone(),
+                                    count: stats.count,
+                                    lines_scanned: stats.lines_scanned,
+                                    matching_lines: stats.matching_lines,
+                                    matched_bytes: stats.matched_bytes,
+                                    match_line_numbers: stats.match_line_numbers,
+                                });
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Error reading file {}: {}", path.display(), e);
+                        }
                     }
                 }
-            }
+            });
         }
-    }
+    });
 
+    let mut results = Arc::try_unwrap(results)
+        .unwrap_or_else(|_| unreachable!("all worker threads have joined by now"))
+        .into_inner()
+        .unwrap();
+    results.sort_by(|a, b| a.path.cmp(&b.path));
     Ok(results)
 }
 
-fn format_results(results: &[FileResult]) -> String {
+fn format_results(results: &[FileResult], elapsed: std::time::Duration) -> String {
     let mut output = String::new();
-    
+
     for result in results {
         output.push_str(&format!("File: {}\n", result.path.display()));
-        output.push_str(&format!("  Count: {}\n\n", result.count));
+        output.push_str(&format!("  Count: {}", result.count));
+        if !result.match_line_numbers.is_empty() {
+            let lines = result
+                .match_line_numbers
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            output.push_str(&format!("  Lines: {}", lines));
+        }
+        output.push_str("\n\n");
     }
-    
+
     let total_files = results.len();
     let total_occurrences: usize = results.iter().map(|r| r.count).sum();
-    
+
     output.push_str(&format!("Total files searched: {}\n", total_files));
     output.push_str(&format!("Total occurrences: {}\n", total_occurrences));
-    
+    output.push_str(&format!("Elapsed: {:.3}s\n", elapsed.as_secs_f64()));
+
+    output
+}
+
+/// Renders results as newline-delimited JSON: one `match` record per file
+/// followed by a trailing `summary` record, mirroring ripgrep's `--json`.
+fn format_results_json(results: &[FileResult], elapsed: std::time::Duration) -> String {
+    let mut output = String::new();
+
+    for result in results {
+        output.push_str(&result.to_json_line());
+        output.push('\n');
+    }
+
+    let total_files = results.len();
+    let total_occurrences: usize = results.iter().map(|r| r.count).sum();
+
+    output.push_str(&format!(
+        "{{\"type\":\"summary\",\"total_files\":{},\"total_occurrences\":{},\"elapsed_secs\":{:.3}}}\n",
+        total_files, total_occurrences, elapsed.as_secs_f64()
+    ));
+
     output
 }
 
 fn main() {
     let args = Args::parse();
 
-    if args.directories.is_empty() {
-        eprintln!("Error: At least one directory must be specified");
-        std::process::exit(1);
+    // No directories, or an explicit "-", means search standard input instead.
+    let read_stdin = args.directories.is_empty()
+        || args.directories.iter().any(|d| d.as_path() == Path::new("-"));
+    let directories: Vec<PathBuf> = args
+        .directories
+        .iter()
+        .filter(|d| d.as_path() != Path::new("-"))
+        .cloned()
+        .collect();
+
+    let matcher = match Matcher::new(&args.pattern, args.case_insensitive, args.regex) {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("Error: invalid pattern: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let output_format = OutputFormat::from_args(&args);
+    let num_threads = args.threads.unwrap_or_else(|| {
+        thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    });
+
+    let filters = WalkFilters::from_args(&args);
+    let forced_encoding = match &args.encoding {
+        Some(label) => match WideEncoding::from_label(label) {
+            Some(encoding) => Some(encoding),
+            None => {
+                eprintln!("Error: unknown encoding: {}", label);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let binary_policy = BinaryPolicy::from_args(&args);
+    let sniff_bytes = args.sniff_bytes;
+    let search_options = SearchOptions {
+        num_threads,
+        forced_encoding,
+        binary_policy,
+        sniff_bytes,
+        track_line_numbers: args.line_numbers,
+    };
+
+    let start = std::time::Instant::now();
+    let search_result = search_directories(&directories, &matcher, &filters, search_options);
+
+    let mut results = match search_result {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if read_stdin {
+        let mut buffer = Vec::new();
+        if let Err(e) = std::io::stdin().lock().read_to_end(&mut buffer) {
+            eprintln!("Error reading <stdin>: {}", e);
+            std::process::exit(1);
+        }
+
+        let should_search = match binary_policy {
+            BinaryPolicy::ForceText | BinaryPolicy::QuitAtNull => true,
+            BinaryPolicy::Skip => {
+                forced_encoding.is_some() || {
+                    let sample = &buffer[..buffer.len().min(sniff_bytes)];
+                    matches!(classify_text_kind(sample), TextKind::Text)
+                }
+            }
+        };
+
+        if should_search {
+            let quit_at_null = matches!(binary_policy, BinaryPolicy::QuitAtNull);
+            match count_pattern_in_bytes(
+                "<stdin>",
+                &buffer,
+                &matcher,
+                forced_encoding,
+                quit_at_null,
+                args.line_numbers,
+            ) {
+                Ok(stats) if stats.count > 0 => results.push(FileResult {
+                    path: PathBuf::from("<stdin>"),
+                    count: stats.count,
+                    lines_scanned: stats.lines_scanned,
+                    matching_lines: stats.matching_lines,
+                    matched_bytes: stats.matched_bytes,
+                    match_line_numbers: stats.match_line_numbers,
+                }),
+                Ok(_) => {}
+                Err(e) => eprintln!("Error reading <stdin>: {}", e),
+            }
+        }
+
+        results.sort_by(|a, b| a.path.cmp(&b.path));
     }
 
-    match search_directories(&args.directories, &args.pattern, args.case_insensitive) {
-        Ok(results) => {
+    let elapsed = start.elapsed();
+
+    match output_format {
+        OutputFormat::Json => print!("{}", format_results_json(&results, elapsed)),
+        OutputFormat::Text => {
             if results.is_empty() {
                 println!("No occurrences found.");
             } else {
-                print!("{}", format_results(&results));
+                print!("{}", format_results(&results, elapsed));
             }
         }
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transcode_utf8_strips_bom() {
+        let mut bytes = vec![0xEF, 0xBB, 0xBF];
+        bytes.extend_from_slice("hello".as_bytes());
+        assert_eq!(transcode_to_utf8(&bytes, None).unwrap(), "hello");
+    }
+
+    #[test]
+    fn transcode_utf8_without_bom_is_unchanged() {
+        assert_eq!(transcode_to_utf8("hello".as_bytes(), None).unwrap(), "hello");
+    }
+
+    #[test]
+    fn transcode_utf16le_strips_bom() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for c in "hello".encode_utf16() {
+            bytes.extend_from_slice(&c.to_le_bytes());
         }
+        assert_eq!(transcode_to_utf8(&bytes, None).unwrap(), "hello");
+    }
+
+    #[test]
+    fn transcode_utf16be_strips_bom() {
+        let mut bytes = vec![0xFE, 0xFF];
+        for c in "hello".encode_utf16() {
+            bytes.extend_from_slice(&c.to_be_bytes());
+        }
+        assert_eq!(transcode_to_utf8(&bytes, None).unwrap(), "hello");
+    }
+
+    #[test]
+    fn transcode_utf32le_strips_bom() {
+        let mut bytes = vec![0xFF, 0xFE, 0x00, 0x00];
+        for c in "hello".chars() {
+            bytes.extend_from_slice(&(c as u32).to_le_bytes());
+        }
+        assert_eq!(transcode_to_utf8(&bytes, None).unwrap(), "hello");
+    }
+
+    #[test]
+    fn transcode_utf32be_strips_bom() {
+        let mut bytes = vec![0x00, 0x00, 0xFE, 0xFF];
+        for c in "hello".chars() {
+            bytes.extend_from_slice(&(c as u32).to_be_bytes());
+        }
+        assert_eq!(transcode_to_utf8(&bytes, None).unwrap(), "hello");
+    }
+
+    #[test]
+    fn literal_matcher_counts_non_overlapping_occurrences() {
+        let matcher = Matcher::new("na", false, false).unwrap();
+        let (count, matched_bytes) = matcher.count_in_line("banana");
+        assert_eq!(count, 2);
+        assert_eq!(matched_bytes, 4);
+    }
+
+    #[test]
+    fn regex_matcher_counts_occurrences() {
+        let matcher = Matcher::new(r"\d+", false, true).unwrap();
+        let (count, matched_bytes) = matcher.count_in_line("a1 bb22 c333");
+        assert_eq!(count, 3);
+        assert_eq!(matched_bytes, "1".len() + "22".len() + "333".len());
+    }
+
+    #[test]
+    fn json_escape_escapes_control_and_special_characters() {
+        assert_eq!(
+            json_escape("a\"b\\c\nd\re\tf\u{1}g"),
+            "\"a\\\"b\\\\c\\nd\\re\\tf\\u0001g\""
+        );
+    }
+
+    #[test]
+    fn json_escape_leaves_plain_text_unchanged() {
+        assert_eq!(json_escape("hello world"), "\"hello world\"");
+    }
+
+    #[test]
+    fn quit_at_null_truncates_before_the_null_byte() {
+        let matcher = Matcher::new("needle", false, false).unwrap();
+        let mut bytes = b"needle before\n".to_vec();
+        bytes.push(0);
+        bytes.extend_from_slice(b"needle after\n");
+
+        let stats = count_pattern_in_bytes("<test>", &bytes, &matcher, None, true, false).unwrap();
+        assert_eq!(stats.count, 1);
+    }
+
+    #[test]
+    fn force_text_ignores_null_bytes() {
+        let matcher = Matcher::new("needle", false, false).unwrap();
+        let mut bytes = b"needle before\n".to_vec();
+        bytes.push(0);
+        bytes.extend_from_slice(b"needle after\n");
+
+        let stats = count_pattern_in_bytes("<test>", &bytes, &matcher, None, false, false).unwrap();
+        assert_eq!(stats.count, 2);
+    }
+
+    #[test]
+    fn read_input_bytes_treats_dev_null_as_empty() {
+        let bytes = read_input_bytes(&PathBuf::from("/dev/null")).unwrap();
+        assert!(bytes.is_empty());
     }
 }